@@ -1,11 +1,68 @@
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+#[cfg(not(feature = "json-persistence"))]
+use rkyv::option::ArchivedOption;
+#[cfg(not(feature = "json-persistence"))]
+use rkyv::{Deserialize as RkyvDeserialize, Infallible};
+
 type SharedTree = Arc<RwLock<Box<KDTree>>>;
 
+/// Entry in the bounded max-heap used by `k_nearest_neighbors`, ordered by
+/// `KDTree::distance` under the tree's metric (squared for Euclidean/Cosine)
+/// so the heap's peek/pop always surfaces the current worst (farthest)
+/// candidate.
+struct HeapEntry {
+    dist: f32,
+    id: String,
+    point: Vec<f32>,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.partial_cmp(&other.dist).unwrap()
+    }
+}
+
+/// Distance metric used for both ordering candidates during search and the
+/// kd-tree's own branch-pruning bound. Mirrors the distance functions the
+/// `kdtree` crate lets callers pass into `nearest`/`iter_nearest`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    not(feature = "json-persistence"),
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub enum Metric {
+    Euclidean,
+    Manhattan,
+    Cosine,
+}
+
+/// Default fraction of the tree's size that may be inserted before
+/// `add` triggers an automatic `rebuild`, keeping query latency bounded
+/// on long-running services instead of degenerating toward a linked list.
+const DEFAULT_REBUILD_THRESHOLD: f32 = 0.3;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct KDTree {
     id: String,
@@ -13,33 +70,396 @@ pub struct KDTree {
     left: Option<Box<KDTree>>,
     right: Option<Box<KDTree>>,
     dimension: usize,
+    metric: Metric,
+    size: usize,
+    inserts_since_rebuild: usize,
+    rebuild_threshold: f32,
+}
+
+/// On-disk node layout for `KDTree`: the same fields as `KDTree` itself, but
+/// with `left`/`right` stored as indices into the enclosing `FlatTree`'s
+/// `nodes` vector rather than nested `Box`es, so the archived buffer is
+/// pointer-free and can be mapped and validated in place.
+#[cfg(not(feature = "json-persistence"))]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+struct FlatNode {
+    id: String,
+    point: Vec<f32>,
+    dimension: usize,
+    metric: Metric,
+    left: Option<u32>,
+    right: Option<u32>,
+}
+
+/// Flattened, pre-order serialization of a `KDTree`; the root is always
+/// `nodes[0]`.
+#[cfg(not(feature = "json-persistence"))]
+#[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize, Debug)]
+#[archive(check_bytes)]
+struct FlatTree {
+    nodes: Vec<FlatNode>,
+}
+
+/// Block compression applied to the serialized payload written by
+/// `save_to_file`, selected the same way `lsm-tree`'s `CompressionType`
+/// picks between a fast codec and a higher-ratio one.
+#[cfg(not(feature = "json-persistence"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Lz4,
+    Miniz,
+}
+
+#[cfg(not(feature = "json-persistence"))]
+impl Compression {
+    fn from_byte(byte: u8) -> Option<Compression> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Lz4),
+            2 => Some(Compression::Miniz),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "json-persistence"))]
+const PERSISTENCE_MAGIC: &[u8; 4] = b"KDTR";
+#[cfg(not(feature = "json-persistence"))]
+const PERSISTENCE_HEADER_FIELDS_LEN: usize = PERSISTENCE_MAGIC.len() + 1 + 8;
+/// rkyv aligns archived buffers to (at least) 8 bytes; `open_mmap` maps the
+/// file at a page-aligned base, so the header is padded out to a multiple
+/// of this alignment to keep the payload that follows it aligned too.
+#[cfg(not(feature = "json-persistence"))]
+const RKYV_ALIGNMENT: usize = 16;
+#[cfg(not(feature = "json-persistence"))]
+const PERSISTENCE_HEADER_LEN: usize =
+    PERSISTENCE_HEADER_FIELDS_LEN.next_multiple_of(RKYV_ALIGNMENT);
+
+#[cfg(not(feature = "json-persistence"))]
+struct PersistenceHeader {
+    compression: Compression,
+    uncompressed_len: usize,
+}
+
+/// Reads the magic bytes, compression type, and uncompressed length
+/// `save_to_file` writes ahead of the payload. Returns `None` when the
+/// magic is absent (or the type byte is unrecognized), so callers can
+/// fall back to treating the whole file as an unprefixed legacy payload.
+#[cfg(not(feature = "json-persistence"))]
+fn parse_persistence_header(contents: &[u8]) -> Option<PersistenceHeader> {
+    if contents.len() < PERSISTENCE_HEADER_LEN
+        || &contents[..PERSISTENCE_MAGIC.len()] != PERSISTENCE_MAGIC
+    {
+        return None;
+    }
+
+    let compression = Compression::from_byte(contents[PERSISTENCE_MAGIC.len()])?;
+    let len_start = PERSISTENCE_MAGIC.len() + 1;
+    let uncompressed_len =
+        u64::from_le_bytes(contents[len_start..len_start + 8].try_into().unwrap()) as usize;
+
+    Some(PersistenceHeader {
+        compression,
+        uncompressed_len,
+    })
+}
+
+/// A `KDTree` persisted with `rkyv` and memory-mapped rather than fully
+/// deserialized; `nearest_neighbor`/`k_nearest_neighbors`/`within` walk the
+/// archived buffer directly. Only an uncompressed file can be mapped this
+/// way — see `KDTree::open_mmap`.
+#[cfg(not(feature = "json-persistence"))]
+pub struct MmapTree {
+    mmap: memmap2::Mmap,
+    payload_offset: usize,
+}
+
+#[cfg(not(feature = "json-persistence"))]
+impl MmapTree {
+    fn archived(&self) -> &ArchivedFlatTree {
+        // SAFETY: the buffer was validated with `check_archived_root` in
+        // `KDTree::open_mmap` before this `MmapTree` was constructed, and
+        // the file is never mutated out from under the mapping.
+        unsafe { rkyv::archived_root::<FlatTree>(&self.mmap[self.payload_offset..]) }
+    }
+
+    fn root_metric(&self) -> Metric {
+        match &self.archived().nodes[0].metric {
+            ArchivedMetric::Euclidean => Metric::Euclidean,
+            ArchivedMetric::Manhattan => Metric::Manhattan,
+            ArchivedMetric::Cosine => Metric::Cosine,
+        }
+    }
+
+    fn query_point(&self, target: &[f32]) -> Vec<f32> {
+        if self.root_metric() == Metric::Cosine {
+            KDTree::normalize(target)
+        } else {
+            target.to_vec()
+        }
+    }
+
+    fn node_metric(node: &ArchivedFlatNode) -> Metric {
+        match &node.metric {
+            ArchivedMetric::Euclidean => Metric::Euclidean,
+            ArchivedMetric::Manhattan => Metric::Manhattan,
+            ArchivedMetric::Cosine => Metric::Cosine,
+        }
+    }
+
+    fn child_index(child: &ArchivedOption<u32>) -> Option<u32> {
+        match child {
+            ArchivedOption::Some(index) => Some(*index),
+            ArchivedOption::None => None,
+        }
+    }
+
+    fn nn_search(&self, index: u32, target: &[f32]) -> (u32, f32) {
+        let nodes = &self.archived().nodes;
+        let node = &nodes[index as usize];
+        let axis = node.dimension as usize;
+        let metric = Self::node_metric(node);
+        let current_distance = KDTree::distance(&node.point, target, metric);
+
+        let (near, far) = if target[axis] < node.point[axis] {
+            (
+                Self::child_index(&node.left),
+                Self::child_index(&node.right),
+            )
+        } else {
+            (
+                Self::child_index(&node.right),
+                Self::child_index(&node.left),
+            )
+        };
+
+        let mut best = if let Some(near) = near {
+            self.nn_search(near, target)
+        } else {
+            (index, current_distance)
+        };
+
+        if current_distance < best.1 {
+            best = (index, current_distance);
+        }
+
+        let plane_distance = KDTree::plane_distance(metric, target[axis] - node.point[axis]);
+        if let Some(far) = far {
+            if plane_distance < best.1 {
+                let far_best = self.nn_search(far, target);
+                if far_best.1 < best.1 {
+                    best = far_best;
+                }
+            }
+        }
+
+        best
+    }
+
+    fn knn_search(&self, index: u32, target: &[f32], k: usize, heap: &mut BinaryHeap<HeapEntry>) {
+        let nodes = &self.archived().nodes;
+        let node = &nodes[index as usize];
+        let axis = node.dimension as usize;
+        let metric = Self::node_metric(node);
+        let current_distance = KDTree::distance(&node.point, target, metric);
+
+        heap.push(HeapEntry {
+            dist: current_distance,
+            id: node.id.to_string(),
+            point: node.point.iter().copied().collect(),
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+
+        let (near, far) = if target[axis] < node.point[axis] {
+            (
+                Self::child_index(&node.left),
+                Self::child_index(&node.right),
+            )
+        } else {
+            (
+                Self::child_index(&node.right),
+                Self::child_index(&node.left),
+            )
+        };
+
+        if let Some(near) = near {
+            self.knn_search(near, target, k, heap);
+        }
+
+        let plane_distance = KDTree::plane_distance(metric, target[axis] - node.point[axis]);
+        if let Some(far) = far {
+            if heap.len() < k || plane_distance < heap.peek().unwrap().dist {
+                self.knn_search(far, target, k, heap);
+            }
+        }
+    }
+
+    fn within_search(
+        &self,
+        index: u32,
+        target: &[f32],
+        radius_bound: f32,
+        results: &mut Vec<(String, Vec<f32>, f32)>,
+    ) {
+        let nodes = &self.archived().nodes;
+        let node = &nodes[index as usize];
+        let axis = node.dimension as usize;
+        let metric = Self::node_metric(node);
+        let current_distance = KDTree::distance(&node.point, target, metric);
+
+        if current_distance <= radius_bound {
+            results.push((
+                node.id.to_string(),
+                node.point.iter().copied().collect(),
+                current_distance,
+            ));
+        }
+
+        let (near, far) = if target[axis] < node.point[axis] {
+            (
+                Self::child_index(&node.left),
+                Self::child_index(&node.right),
+            )
+        } else {
+            (
+                Self::child_index(&node.right),
+                Self::child_index(&node.left),
+            )
+        };
+
+        if let Some(near) = near {
+            self.within_search(near, target, radius_bound, results);
+        }
+
+        let plane_distance = KDTree::plane_distance(metric, target[axis] - node.point[axis]);
+        if let Some(far) = far {
+            if plane_distance <= radius_bound {
+                self.within_search(far, target, radius_bound, results);
+            }
+        }
+    }
+
+    pub fn nearest_neighbor(&self, target: &[f32]) -> (String, Vec<f32>) {
+        let target = self.query_point(target);
+        let (index, _) = self.nn_search(0, &target);
+        let node = &self.archived().nodes[index as usize];
+        (node.id.to_string(), node.point.iter().copied().collect())
+    }
+
+    pub fn k_nearest_neighbors(&self, target: &[f32], k: usize) -> Vec<(String, Vec<f32>, f32)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let target = self.query_point(target);
+        let metric = self.root_metric();
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.knn_search(0, &target, k, &mut heap);
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.id,
+                    entry.point,
+                    KDTree::reported_distance(metric, entry.dist),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns all stored points within `radius` of `target`, sorted
+    /// ascending by actual distance (not the squared value used internally
+    /// for Euclidean/Cosine pruning).
+    pub fn within(&self, target: &[f32], radius: f32) -> Vec<(String, Vec<f32>, f32)> {
+        let metric = self.root_metric();
+        let target = self.query_point(target);
+        let radius_bound = KDTree::radius_bound(metric, radius);
+
+        let mut results = Vec::new();
+        self.within_search(0, &target, radius_bound, &mut results);
+        for result in results.iter_mut() {
+            result.2 = KDTree::reported_distance(metric, result.2);
+        }
+        results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+        results
+    }
 }
 
 impl KDTree {
-    pub fn new(data: &[Vec<f32>], ids: &[String], depth: usize) -> Option<Box<KDTree>> {
-        let k = data.get(0)?.len();
+    pub fn new(
+        data: &[Vec<f32>],
+        ids: &[String],
+        depth: usize,
+        metric: Metric,
+    ) -> Option<Box<KDTree>> {
+        let k = data.first()?.len();
         let axis = depth % k;
 
-        let mut data = data.to_vec();
-        data.sort_by(|a, b| a[axis].partial_cmp(&b[axis]).unwrap());
+        let mut pairs: Vec<(Vec<f32>, String)> =
+            data.to_vec().into_iter().zip(ids.to_vec()).collect();
+        if metric == Metric::Cosine {
+            for (point, _) in pairs.iter_mut() {
+                *point = Self::normalize(point);
+            }
+        }
+        pairs.sort_by(|a, b| a.0[axis].partial_cmp(&b.0[axis]).unwrap());
 
-        let median = data.len() / 2;
+        let median = pairs.len() / 2;
+        let (data, ids): (Vec<Vec<f32>>, Vec<String>) = pairs.into_iter().unzip();
 
-        let node = KDTree {
+        let mut node = KDTree {
             id: ids[median].clone(),
             point: data[median].clone(),
-            left: KDTree::new(&data[..median], &ids[..median], depth + 1),
-            right: KDTree::new(&data[median + 1..], &ids[median + 1..], depth + 1),
+            left: KDTree::new(&data[..median], &ids[..median], depth + 1, metric),
+            right: KDTree::new(&data[median + 1..], &ids[median + 1..], depth + 1, metric),
             dimension: axis,
+            metric,
+            size: 0,
+            inserts_since_rebuild: 0,
+            rebuild_threshold: DEFAULT_REBUILD_THRESHOLD,
         };
+        node.finalize_size();
 
         Some(Box::new(node))
     }
 
+    /// Sets `size` to 1 (this node) plus its children's already-known
+    /// sizes; children must be finalized first, which holds both in `new`
+    /// (built bottom-up) and `from_flat` (children built before parent).
+    fn finalize_size(&mut self) {
+        let left_size = self.left.as_ref().map_or(0, |left| left.size);
+        let right_size = self.right.as_ref().map_or(0, |right| right.size);
+        self.size = 1 + left_size + right_size;
+    }
+
+    /// Sets the fraction of the tree's size that may be inserted before an
+    /// automatic rebuild is triggered (default [`DEFAULT_REBUILD_THRESHOLD`]).
+    pub fn with_rebuild_threshold(mut self: Box<Self>, threshold: f32) -> Box<Self> {
+        self.rebuild_threshold = threshold;
+        self
+    }
+
+    /// Rescales `point` to unit length so a dot product between normalized
+    /// vectors maps monotonically to cosine similarity. Leaves zero vectors
+    /// untouched to avoid dividing by zero.
+    fn normalize(point: &[f32]) -> Vec<f32> {
+        let norm = point.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            point.to_vec()
+        } else {
+            point.iter().map(|x| x / norm).collect()
+        }
+    }
+
     fn nn_search(&self, target: &[f32]) -> (&String, &Vec<f32>, f32) {
         let axis = self.dimension;
         let current_point = &self.point;
-        let current_distance = Self::distance_squared(current_point, target);
+        let current_distance = Self::distance(current_point, target, self.metric);
 
         let next_branch = if target[axis] < current_point[axis] {
             &self.left
@@ -63,9 +483,10 @@ impl KDTree {
             &self.left
         };
 
-        if other_branch.is_some() && target[axis].powf(2.0) < best.2 {
+        let plane_distance = Self::plane_distance(self.metric, target[axis] - current_point[axis]);
+        if other_branch.is_some() && plane_distance < best.2 {
             let other_best = other_branch.as_ref().unwrap().nn_search(target);
-            if other_best.1 < best.1 {
+            if other_best.2 < best.2 {
                 best = other_best;
             }
         }
@@ -73,6 +494,44 @@ impl KDTree {
         best
     }
 
+    /// Pushes this node (and recurses into its children) onto a bounded
+    /// max-heap of size `k`, keyed on `distance` to `target` under
+    /// `self.metric` (squared for Euclidean/Cosine). The
+    /// near child is always visited; the far child is only visited when
+    /// the heap isn't full yet or the splitting plane is closer than the
+    /// current worst candidate.
+    fn knn_search(&self, target: &[f32], k: usize, heap: &mut BinaryHeap<HeapEntry>) {
+        let axis = self.dimension;
+        let current_point = &self.point;
+        let current_distance = Self::distance(current_point, target, self.metric);
+
+        heap.push(HeapEntry {
+            dist: current_distance,
+            id: self.id.clone(),
+            point: current_point.clone(),
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+
+        let (near_branch, far_branch) = if target[axis] < current_point[axis] {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        };
+
+        if let Some(near) = near_branch {
+            near.knn_search(target, k, heap);
+        }
+
+        let plane_distance = Self::plane_distance(self.metric, target[axis] - current_point[axis]);
+        if let Some(far) = far_branch {
+            if heap.len() < k || plane_distance < heap.peek().unwrap().dist {
+                far.knn_search(target, k, heap);
+            }
+        }
+    }
+
     fn distance_squared(p1: &[f32], p2: &[f32]) -> f32 {
         p1.iter()
             .zip(p2.iter())
@@ -80,6 +539,171 @@ impl KDTree {
             .sum()
     }
 
+    /// Distance between two points under `metric`. Euclidean and Cosine
+    /// (whose vectors are normalized on insertion/query) both compare
+    /// squared Euclidean distance, since that ordering is monotonic with
+    /// cosine similarity for unit vectors; Manhattan sums absolute
+    /// per-axis differences.
+    fn distance(p1: &[f32], p2: &[f32], metric: Metric) -> f32 {
+        match metric {
+            Metric::Euclidean | Metric::Cosine => Self::distance_squared(p1, p2),
+            Metric::Manhattan => p1
+                .iter()
+                .zip(p2.iter())
+                .map(|(x1, x2)| (x1 - x2).abs())
+                .sum(),
+        }
+    }
+
+    /// Lower bound on the distance (under `metric`) from `target` to the
+    /// far side of the splitting plane, used to decide whether the far
+    /// branch can be pruned. `axis_diff` is the raw (unsquared,
+    /// unsigned-by-caller) difference along the splitting axis.
+    fn plane_distance(metric: Metric, axis_diff: f32) -> f32 {
+        match metric {
+            Metric::Euclidean | Metric::Cosine => axis_diff.powi(2),
+            Metric::Manhattan => axis_diff.abs(),
+        }
+    }
+
+    /// Flattens this subtree into `nodes` in pre-order (self, then left,
+    /// then right) and returns the index it was written at.
+    #[cfg(not(feature = "json-persistence"))]
+    fn flatten_into(&self, nodes: &mut Vec<FlatNode>) -> u32 {
+        let index = nodes.len() as u32;
+        nodes.push(FlatNode {
+            id: self.id.clone(),
+            point: self.point.clone(),
+            dimension: self.dimension,
+            metric: self.metric,
+            left: None,
+            right: None,
+        });
+
+        nodes[index as usize].left = self.left.as_ref().map(|left| left.flatten_into(nodes));
+        nodes[index as usize].right = self.right.as_ref().map(|right| right.flatten_into(nodes));
+
+        index
+    }
+
+    #[cfg(not(feature = "json-persistence"))]
+    fn from_flat(nodes: &[FlatNode], index: u32) -> Box<KDTree> {
+        let node = &nodes[index as usize];
+        let mut tree = KDTree {
+            id: node.id.clone(),
+            point: node.point.clone(),
+            dimension: node.dimension,
+            metric: node.metric,
+            left: node.left.map(|i| Self::from_flat(nodes, i)),
+            right: node.right.map(|i| Self::from_flat(nodes, i)),
+            size: 0,
+            inserts_since_rebuild: 0,
+            rebuild_threshold: DEFAULT_REBUILD_THRESHOLD,
+        };
+        tree.finalize_size();
+        Box::new(tree)
+    }
+
+    #[cfg(not(feature = "json-persistence"))]
+    pub async fn save_to_file(
+        tree: &SharedTree,
+        filename: &str,
+        compression: Compression,
+    ) -> std::io::Result<()> {
+        let tree = tree.read().await;
+        let mut nodes = Vec::new();
+        tree.flatten_into(&mut nodes);
+        let flat = FlatTree { nodes };
+
+        let bytes = rkyv::to_bytes::<_, 1024>(&flat)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        let payload = match compression {
+            Compression::None => bytes.to_vec(),
+            Compression::Lz4 => lz4_flex::compress(&bytes),
+            Compression::Miniz => miniz_oxide::deflate::compress_to_vec(&bytes, 6),
+        };
+
+        let mut file = File::create(filename)?;
+        file.write_all(PERSISTENCE_MAGIC)?;
+        file.write_all(&[compression as u8])?;
+        file.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        file.write_all(&[0u8; PERSISTENCE_HEADER_LEN - PERSISTENCE_HEADER_FIELDS_LEN])?;
+        file.write_all(&payload)?;
+        Ok(())
+    }
+
+    /// Reverses the header/compression applied by `save_to_file`, falling
+    /// back to treating `contents` as a bare rkyv buffer when the magic is
+    /// absent so files written before this header existed still load.
+    #[cfg(not(feature = "json-persistence"))]
+    fn decode_persisted_bytes(contents: &[u8]) -> std::io::Result<Vec<u8>> {
+        let Some(header) = parse_persistence_header(contents) else {
+            return Ok(contents.to_vec());
+        };
+        let payload = &contents[PERSISTENCE_HEADER_LEN..];
+
+        let bytes = match header.compression {
+            Compression::None => payload.to_vec(),
+            Compression::Lz4 => lz4_flex::decompress(payload, header.uncompressed_len)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?,
+            Compression::Miniz => {
+                miniz_oxide::inflate::decompress_to_vec(payload).map_err(|e| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{e:?}"))
+                })?
+            }
+        };
+        Ok(bytes)
+    }
+
+    #[cfg(not(feature = "json-persistence"))]
+    pub async fn load_from_file(filename: &str) -> std::io::Result<SharedTree> {
+        let mut file = File::open(filename)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        let bytes = Self::decode_persisted_bytes(&contents)?;
+
+        let archived = rkyv::check_archived_root::<FlatTree>(&bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        let flat: FlatTree = archived
+            .deserialize(&mut Infallible)
+            .expect("FlatTree deserialization is infallible");
+
+        Ok(Arc::new(RwLock::new(KDTree::from_flat(&flat.nodes, 0))))
+    }
+
+    /// Memory-maps a tree persisted by `save_to_file` and validates it with
+    /// `bytecheck`, without deserializing it into owned `KDTree` nodes.
+    /// Only files written with `Compression::None` (or legacy headerless
+    /// files, which are implicitly uncompressed) can be mapped this way,
+    /// since decompression can't happen in place; compressed files must go
+    /// through `load_from_file` instead.
+    #[cfg(not(feature = "json-persistence"))]
+    pub fn open_mmap(filename: &str) -> std::io::Result<MmapTree> {
+        let file = File::open(filename)?;
+        // SAFETY: the file isn't expected to be modified by another process
+        // while mapped; `MmapTree` only ever reads through this mapping.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        let payload_offset = match parse_persistence_header(&mmap) {
+            None => 0,
+            Some(header) if header.compression == Compression::None => PERSISTENCE_HEADER_LEN,
+            Some(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "open_mmap requires an uncompressed file; use load_from_file for compressed persistence",
+                ))
+            }
+        };
+
+        rkyv::check_archived_root::<FlatTree>(&mmap[payload_offset..])
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(MmapTree {
+            mmap,
+            payload_offset,
+        })
+    }
+
+    #[cfg(feature = "json-persistence")]
     pub async fn save_to_file(tree: &SharedTree, filename: &str) -> std::io::Result<()> {
         let tree = tree.read().await;
         let mut file = File::create(filename)?;
@@ -88,6 +712,7 @@ impl KDTree {
         Ok(())
     }
 
+    #[cfg(feature = "json-persistence")]
     pub async fn load_from_file(filename: &str) -> std::io::Result<SharedTree> {
         let mut file = File::open(filename)?;
         let mut contents = String::new();
@@ -98,17 +723,142 @@ impl KDTree {
 
     pub async fn nearest_neighbor(tree: &SharedTree, target: &[f32]) -> (String, Vec<f32>) {
         let tree = tree.read().await;
-        let (id, point, _) = tree.nn_search(target);
+        let target = tree.query_point(target);
+        let (id, point, _) = tree.nn_search(&target);
         (id.clone(), point.clone())
     }
 
+    pub async fn k_nearest_neighbors(
+        tree: &SharedTree,
+        target: &[f32],
+        k: usize,
+    ) -> Vec<(String, Vec<f32>, f32)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let tree = tree.read().await;
+        let target = tree.query_point(target);
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        tree.knn_search(&target, k, &mut heap);
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.id,
+                    entry.point,
+                    Self::reported_distance(tree.metric, entry.dist),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns all stored points within `radius` of `target`, sorted
+    /// ascending by actual distance (not the squared value used internally
+    /// for Euclidean/Cosine pruning).
+    pub async fn within(
+        tree: &SharedTree,
+        target: &[f32],
+        radius: f32,
+    ) -> Vec<(String, Vec<f32>, f32)> {
+        let tree = tree.read().await;
+        let target = tree.query_point(target);
+        let radius_bound = Self::radius_bound(tree.metric, radius);
+
+        let mut results = Vec::new();
+        tree.within_search(&target, radius_bound, &mut results);
+        for result in results.iter_mut() {
+            result.2 = Self::reported_distance(tree.metric, result.2);
+        }
+        results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+        results
+    }
+
+    /// Collects every node within `radius_bound` of `target` (a distance
+    /// already scaled to match `self.distance`'s units for `self.metric`,
+    /// e.g. squared for Euclidean/Cosine) into `results`. Always descends
+    /// the near child; the far child is only visited when the splitting
+    /// plane falls within the query ball.
+    fn within_search(
+        &self,
+        target: &[f32],
+        radius_bound: f32,
+        results: &mut Vec<(String, Vec<f32>, f32)>,
+    ) {
+        let axis = self.dimension;
+        let current_point = &self.point;
+        let current_distance = Self::distance(current_point, target, self.metric);
+
+        if current_distance <= radius_bound {
+            results.push((self.id.clone(), current_point.clone(), current_distance));
+        }
+
+        let (near_branch, far_branch) = if target[axis] < current_point[axis] {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        };
+
+        if let Some(near) = near_branch {
+            near.within_search(target, radius_bound, results);
+        }
+
+        let plane_distance = Self::plane_distance(self.metric, target[axis] - current_point[axis]);
+        if let Some(far) = far_branch {
+            if plane_distance <= radius_bound {
+                far.within_search(target, radius_bound, results);
+            }
+        }
+    }
+
+    /// Converts a caller-supplied radius into the bound `within_search`
+    /// compares against, matching the units `distance`/`plane_distance`
+    /// use for `metric` (squared for Euclidean/Cosine, linear for
+    /// Manhattan).
+    fn radius_bound(metric: Metric, radius: f32) -> f32 {
+        match metric {
+            Metric::Euclidean | Metric::Cosine => radius.powi(2),
+            Metric::Manhattan => radius,
+        }
+    }
+
+    /// Converts a `distance`-valued internal bound back into actual
+    /// metric-space distance for public APIs (`within`/`k_nearest_neighbors`
+    /// report real distance, not the squared value used for pruning).
+    fn reported_distance(metric: Metric, distance: f32) -> f32 {
+        match metric {
+            Metric::Euclidean | Metric::Cosine => distance.sqrt(),
+            Metric::Manhattan => distance,
+        }
+    }
+
+    /// Normalizes `target` when the tree's metric is Cosine, matching the
+    /// normalization applied to stored points, so raw caller input can be
+    /// compared against them directly.
+    fn query_point(&self, target: &[f32]) -> Vec<f32> {
+        if self.metric == Metric::Cosine {
+            Self::normalize(target)
+        } else {
+            target.to_vec()
+        }
+    }
+
     pub async fn add(tree: &SharedTree, id: &str, point: &[f32]) {
         let mut tree = tree.write().await;
-        tree.add_recursive(id, point, 0);
+        let point = tree.query_point(point);
+        tree.add_recursive(id, &point, 0);
+        tree.size += 1;
+        tree.inserts_since_rebuild += 1;
+
+        if tree.inserts_since_rebuild as f32 > tree.size as f32 * tree.rebuild_threshold {
+            tree.rebuild_in_place();
+        }
     }
 
     fn add_recursive(&mut self, id: &str, point: &[f32], depth: usize) {
         let axis = self.dimension;
+        let metric = self.metric;
         let direction = if point[axis] < self.point[axis] {
             &mut self.left
         } else {
@@ -118,8 +868,174 @@ impl KDTree {
         if let Some(subtree) = direction {
             subtree.add_recursive(id, point, depth + 1)
         } else {
-            *direction = KDTree::new(&[point.to_vec()], &[id.to_string()], depth + 1);
+            *direction = KDTree::new(&[point.to_vec()], &[id.to_string()], depth + 1, metric);
+        }
+    }
+
+    pub async fn rebuild(tree: &SharedTree) {
+        let mut tree = tree.write().await;
+        tree.rebuild_in_place();
+    }
+
+    /// Collects every stored point via an in-order traversal and rebuilds
+    /// a balanced tree from scratch with `KDTree::new`, the same
+    /// median-split logic used for the initial construction. Resets
+    /// `inserts_since_rebuild`, undoing the skew `add_recursive`'s
+    /// leaf-only insertion accumulates over time.
+    fn rebuild_in_place(&mut self) {
+        let mut ids = Vec::with_capacity(self.size);
+        let mut data = Vec::with_capacity(self.size);
+        self.collect_in_order(&mut ids, &mut data);
+
+        let mut rebuilt = KDTree::new(&data, &ids, 0, self.metric)
+            .expect("rebuilding a non-empty tree always yields a root");
+        rebuilt.rebuild_threshold = self.rebuild_threshold;
+
+        *self = *rebuilt;
+    }
+
+    fn collect_in_order(&self, ids: &mut Vec<String>, data: &mut Vec<Vec<f32>>) {
+        if let Some(left) = &self.left {
+            left.collect_in_order(ids, data);
+        }
+        ids.push(self.id.clone());
+        data.push(self.point.clone());
+        if let Some(right) = &self.right {
+            right.collect_in_order(ids, data);
+        }
+    }
+
+    /// Removes the point with the given `id` from the tree, repairing the
+    /// kd-tree invariant by promoting an in-order successor. Returns `false`
+    /// if `id` isn't present.
+    ///
+    /// One caveat: `SharedTree`'s `Box<KDTree>` layout has no way to
+    /// represent an empty tree, so removing the sole remaining id from a
+    /// single-node tree also returns `false` and leaves that node in place
+    /// — `remove` on the very last id is a no-op, not a real deletion. Every
+    /// other case (including removing down to one node) works as expected.
+    pub async fn remove(tree: &SharedTree, id: &str) -> bool {
+        let mut tree = tree.write().await;
+        let removed = tree.remove_recursive(id);
+        if removed {
+            tree.size = tree.size.saturating_sub(1);
+            tree.inserts_since_rebuild += 1;
+
+            if tree.size > 0
+                && tree.inserts_since_rebuild as f32 > tree.size as f32 * tree.rebuild_threshold
+            {
+                tree.rebuild_in_place();
+            }
+        }
+        removed
+    }
+
+    /// Removes `id` from this subtree or one of its descendants. Matches
+    /// on `self` itself (the entry point for the top-level call from
+    /// `remove`, which has no parent slot to detach); matches on a child
+    /// are repaired via `remove_child`, which can detach a leaf child.
+    /// Returns `false` if `id` isn't found, or if it names a leaf with no
+    /// parent in this call (only possible for a single-node tree, which
+    /// this non-optional layout can't represent as empty).
+    fn remove_recursive(&mut self, id: &str) -> bool {
+        if self.id == id {
+            return self.replace_with_successor();
+        }
+
+        if self.left.as_deref().is_some_and(|left| left.id == id) {
+            return Self::remove_child(&mut self.left);
+        }
+        if let Some(left) = self.left.as_mut() {
+            if left.remove_recursive(id) {
+                return true;
+            }
+        }
+
+        if self.right.as_deref().is_some_and(|right| right.id == id) {
+            return Self::remove_child(&mut self.right);
+        }
+        if let Some(right) = self.right.as_mut() {
+            if right.remove_recursive(id) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Removes the node at `child`, known to exist, by replacing it with
+    /// its in-order successor if it has any children, or detaching it
+    /// entirely if it's a leaf.
+    fn remove_child(child: &mut Option<Box<KDTree>>) -> bool {
+        let node = child.as_mut().expect("caller checked child is Some");
+        if node.replace_with_successor() {
+            true
+        } else {
+            *child = None;
+            true
+        }
+    }
+
+    /// Replaces this node's id/point with its in-order successor — the
+    /// node minimizing the coordinate on `self.dimension` in the right
+    /// subtree, or the left subtree treated as the right if there is no
+    /// right child (the usual trick to avoid an invalid "max of left"
+    /// case) — and recursively deletes that successor from where it was
+    /// found. Returns `false`, leaving the node untouched, when it's a
+    /// leaf and there is no successor to promote.
+    fn replace_with_successor(&mut self) -> bool {
+        if let Some(right) = &mut self.right {
+            let (successor_id, successor_point) =
+                Self::find_min(Some(right), self.dimension).expect("right subtree is non-empty");
+            self.id = successor_id.clone();
+            self.point = successor_point;
+            if right.id == successor_id {
+                Self::remove_child(&mut self.right);
+            } else {
+                right.remove_recursive(&successor_id);
+            }
+            true
+        } else if let Some(left) = self.left.take() {
+            // Standard trick: with no right subtree, the left subtree
+            // takes its place so the right->left dimension invariant
+            // still holds after the copy-up.
+            self.right = Some(left);
+            let right = self.right.as_mut().expect("just assigned Some above");
+            let (successor_id, successor_point) =
+                Self::find_min(Some(right), self.dimension).expect("left subtree is non-empty");
+            self.id = successor_id.clone();
+            self.point = successor_point;
+            if right.id == successor_id {
+                Self::remove_child(&mut self.right);
+            } else {
+                right.remove_recursive(&successor_id);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Finds the id/point minimizing the coordinate on `dimension` within
+    /// `subtree`, searching both children at every level since the
+    /// splitting axis changes per depth — the minimum isn't necessarily
+    /// in whichever branch the axis would normally guide a search into.
+    fn find_min(subtree: Option<&KDTree>, dimension: usize) -> Option<(String, Vec<f32>)> {
+        let node = subtree?;
+        let mut best = (node.id.clone(), node.point.clone());
+
+        if let Some(candidate) = Self::find_min(node.left.as_deref(), dimension) {
+            if candidate.1[dimension] < best.1[dimension] {
+                best = candidate;
+            }
+        }
+        if let Some(candidate) = Self::find_min(node.right.as_deref(), dimension) {
+            if candidate.1[dimension] < best.1[dimension] {
+                best = candidate;
+            }
         }
+
+        Some(best)
     }
 }
 
@@ -144,7 +1060,7 @@ mod tests {
             .into_iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
-        let tree = KDTree::new(&data, &ids, 0).unwrap();
+        let tree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
         assert_eq!(tree.point, vec![3.0, 3.0]);
         assert_eq!(tree.left.as_ref().unwrap().point, vec![2.0, 2.0]);
         assert_eq!(tree.right.as_ref().unwrap().point, vec![5.0, 5.0]);
@@ -157,7 +1073,7 @@ mod tests {
             .into_iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
-        let tree = KDTree::new(&data, &ids, 0).unwrap();
+        let tree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
         let target = &[4.0, 4.0];
         let (_, nearest_neighbor, distance) = tree.nn_search(target);
         assert_eq!(nearest_neighbor, &vec![4.0, 4.0]);
@@ -168,6 +1084,97 @@ mod tests {
         assert!(distance < 0.9999999);
     }
 
+    #[tokio::test]
+    async fn test_k_nearest_neighbors() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        let target = &[4.2, 4.2];
+        let results = KDTree::k_nearest_neighbors(&tree, target, 3).await;
+        assert_eq!(results.len(), 3);
+
+        let returned_ids: Vec<&str> = results.iter().map(|(id, _, _)| id.as_str()).collect();
+        assert_eq!(returned_ids, vec!["d", "e", "c"]);
+
+        // Reported distance is actual Euclidean distance, not squared.
+        let expected = ((4.2f32 - 4.0).powi(2) * 2.0).sqrt();
+        assert!((results[0].2 - expected).abs() < 1e-5);
+
+        for pair in results.windows(2) {
+            assert!(pair[0].2 <= pair[1].2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_k_nearest_neighbors_zero_k_returns_empty() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        let results = KDTree::k_nearest_neighbors(&tree, &[4.2, 4.2], 0).await;
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cosine_metric_normalizes_points() {
+        let data = vec![vec![1.0, 0.0], vec![10.0, 0.0], vec![0.0, 1.0]];
+        let ids = vec!["close", "far_same_direction", "orthogonal"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Cosine).unwrap(),
+        ));
+
+        // Under cosine similarity, a point pointing in the same direction
+        // should win over a nearer-in-raw-distance orthogonal point.
+        let target = &[2.0, 0.0];
+        let (nearest_id, nearest_point) = KDTree::nearest_neighbor(&tree, target).await;
+        assert!(nearest_id == "close" || nearest_id == "far_same_direction");
+        let norm: f32 = nearest_point.iter().map(|x| x * x).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_within() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        let target = &[3.0, 3.0];
+        let results = KDTree::within(&tree, target, 2.0).await;
+
+        let mut returned_ids: Vec<&str> = results.iter().map(|(id, _, _)| id.as_str()).collect();
+        returned_ids.sort();
+        assert_eq!(returned_ids, vec!["b", "c", "d"]);
+        assert_eq!(results[0].0, "c");
+        assert_eq!(results[0].2, 0.0);
+
+        // Reported distance is actual Euclidean distance, not squared.
+        let b_or_d = results.iter().find(|(id, _, _)| id == "b").unwrap();
+        assert!((b_or_d.2 - 2.0f32.sqrt()).abs() < 1e-5);
+
+        for pair in results.windows(2) {
+            assert!(pair[0].2 <= pair[1].2);
+        }
+    }
+
     #[test]
     fn test_distance_squared() {
         let p1 = &[1.0, 1.0];
@@ -176,6 +1183,29 @@ mod tests {
         assert_eq!(distance, 18.0);
     }
 
+    #[cfg(not(feature = "json-persistence"))]
+    #[tokio::test]
+    async fn test_save_load_tree() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let kdtree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
+        let tree = Arc::new(RwLock::new(kdtree));
+        let filename = "test_tree.rkyv";
+
+        KDTree::save_to_file(&tree, filename, Compression::None)
+            .await
+            .unwrap();
+        let loaded_tree = KDTree::load_from_file(filename).await.unwrap();
+
+        let target = &[4.0, 4.0];
+        let nearest_neighbor = KDTree::nearest_neighbor(&loaded_tree, target).await;
+        assert_eq!(nearest_neighbor.1, vec![4.0, 4.0]);
+    }
+
+    #[cfg(feature = "json-persistence")]
     #[tokio::test]
     async fn test_save_load_tree() {
         let data = create_test_data();
@@ -183,7 +1213,7 @@ mod tests {
             .into_iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
-        let kdtree = KDTree::new(&data, &ids, 0).unwrap();
+        let kdtree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
         let tree = Arc::new(RwLock::new(kdtree));
         let filename = "test_tree.json";
 
@@ -195,6 +1225,86 @@ mod tests {
         assert_eq!(nearest_neighbor.1, vec![4.0, 4.0]);
     }
 
+    #[cfg(not(feature = "json-persistence"))]
+    #[tokio::test]
+    async fn test_open_mmap() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let kdtree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
+        let tree = Arc::new(RwLock::new(kdtree));
+        let filename = "test_tree_mmap.rkyv";
+
+        KDTree::save_to_file(&tree, filename, Compression::None)
+            .await
+            .unwrap();
+        let mmap_tree = KDTree::open_mmap(filename).unwrap();
+
+        let (nearest_id, nearest_point) = mmap_tree.nearest_neighbor(&[4.0, 4.0]);
+        assert_eq!(nearest_id, "d");
+        assert_eq!(nearest_point, vec![4.0, 4.0]);
+
+        let knn = mmap_tree.k_nearest_neighbors(&[4.2, 4.2], 3);
+        assert_eq!(knn.len(), 3);
+
+        assert!(mmap_tree.k_nearest_neighbors(&[4.2, 4.2], 0).is_empty());
+
+        let within = mmap_tree.within(&[3.0, 3.0], 2.0);
+        let mut within_ids: Vec<&str> = within.iter().map(|(id, _, _)| id.as_str()).collect();
+        within_ids.sort();
+        assert_eq!(within_ids, vec!["b", "c", "d"]);
+    }
+
+    #[cfg(not(feature = "json-persistence"))]
+    #[tokio::test]
+    async fn test_save_load_tree_with_compression() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let kdtree = KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap();
+        let tree = Arc::new(RwLock::new(kdtree));
+
+        for (filename, compression) in [
+            ("test_tree_lz4.rkyv", Compression::Lz4),
+            ("test_tree_miniz.rkyv", Compression::Miniz),
+        ] {
+            KDTree::save_to_file(&tree, filename, compression)
+                .await
+                .unwrap();
+            let loaded_tree = KDTree::load_from_file(filename).await.unwrap();
+
+            let target = &[4.0, 4.0];
+            let nearest_neighbor = KDTree::nearest_neighbor(&loaded_tree, target).await;
+            assert_eq!(nearest_neighbor.1, vec![4.0, 4.0]);
+
+            // `open_mmap` can't decompress in place and must reject the file.
+            assert!(KDTree::open_mmap(filename).is_err());
+        }
+    }
+
+    #[cfg(not(feature = "json-persistence"))]
+    #[test]
+    fn test_load_from_file_without_header_is_backward_compatible() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+
+        let mut nodes = Vec::new();
+        KDTree::new(&data, &ids, 0, Metric::Euclidean)
+            .unwrap()
+            .flatten_into(&mut nodes);
+        let bytes = rkyv::to_bytes::<_, 1024>(&FlatTree { nodes }).unwrap();
+
+        // A pre-compression file has no magic/header, just the raw rkyv bytes.
+        assert!(KDTree::decode_persisted_bytes(&bytes).unwrap() == bytes.to_vec());
+    }
+
     #[tokio::test]
     async fn test_add() {
         let data = create_test_data();
@@ -202,7 +1312,9 @@ mod tests {
             .into_iter()
             .map(|s| s.to_string())
             .collect::<Vec<String>>();
-        let tree = Arc::new(RwLock::new(KDTree::new(&data, &ids, 0).unwrap()));
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
 
         KDTree::add(&tree, "f", &[6.0, 6.0]).await;
 
@@ -211,4 +1323,164 @@ mod tests {
         assert_eq!(nearest_id, "f");
         assert_eq!(nearest_point, vec![6.0, 6.0]);
     }
+
+    #[tokio::test]
+    async fn test_rebuild_resets_insert_counter() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        KDTree::add(&tree, "f", &[6.0, 6.0]).await;
+        KDTree::add(&tree, "g", &[7.0, 7.0]).await;
+        KDTree::rebuild(&tree).await;
+
+        {
+            let guard = tree.read().await;
+            assert_eq!(guard.size, 7);
+            assert_eq!(guard.inserts_since_rebuild, 0);
+        }
+
+        let (nearest_id, nearest_point) = KDTree::nearest_neighbor(&tree, &[7.0, 7.0]).await;
+        assert_eq!(nearest_id, "g");
+        assert_eq!(nearest_point, vec![7.0, 7.0]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_keeps_ids_matched_to_points() {
+        // Deliberately not sorted along either axis, so a rebuild that
+        // re-sorts points without carrying ids along would scramble them.
+        let data = vec![
+            vec![5.0, 2.0],
+            vec![1.0, 4.0],
+            vec![3.0, 1.0],
+            vec![4.0, 5.0],
+            vec![2.0, 3.0],
+        ];
+        let ids = vec!["e", "a", "c", "d", "b"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        KDTree::rebuild(&tree).await;
+
+        for (id, point) in ids.iter().zip(data.iter()) {
+            let (nearest_id, nearest_point) = KDTree::nearest_neighbor(&tree, point).await;
+            assert_eq!(&nearest_id, id);
+            assert_eq!(&nearest_point, point);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_triggers_automatic_rebuild() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean)
+                .unwrap()
+                .with_rebuild_threshold(0.3),
+        ));
+
+        for i in 0..3 {
+            let val = 10.0 + i as f32;
+            KDTree::add(&tree, &format!("extra{i}"), &[val, val]).await;
+        }
+
+        let guard = tree.read().await;
+        assert!(
+            guard.inserts_since_rebuild < 3,
+            "expected at least one automatic rebuild to reset the insert counter"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_leaf() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        assert!(KDTree::remove(&tree, "e").await);
+
+        let guard = tree.read().await;
+        assert_eq!(guard.size, 4);
+        drop(guard);
+
+        let results = KDTree::within(&tree, &[5.0, 5.0], 0.01).await;
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_internal_node_with_children() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        // "c" (3.0, 3.0) is the root built by the median split; removing
+        // it must repair both subtrees, not just drop a leaf.
+        assert!(KDTree::remove(&tree, "c").await);
+
+        let guard = tree.read().await;
+        assert_eq!(guard.size, 4);
+        assert_ne!(guard.id, "c");
+        drop(guard);
+
+        for remaining in ["a", "b", "d", "e"] {
+            let results = KDTree::k_nearest_neighbors(&tree, &[0.0, 0.0], 4).await;
+            assert!(results.iter().any(|(id, _, _)| id == remaining));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_missing_id_returns_false() {
+        let data = create_test_data();
+        let ids = vec!["a", "b", "c", "d", "e"]
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        assert!(!KDTree::remove(&tree, "nonexistent").await);
+
+        let guard = tree.read().await;
+        assert_eq!(guard.size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_remove_last_node_is_a_documented_no_op() {
+        let data = vec![vec![1.0, 1.0]];
+        let ids = vec!["a".to_string()];
+        let tree = Arc::new(RwLock::new(
+            KDTree::new(&data, &ids, 0, Metric::Euclidean).unwrap(),
+        ));
+
+        // The `Box<KDTree>` layout can't represent an empty tree, so the
+        // sole remaining id can't actually be deleted — see `KDTree::remove`.
+        assert!(!KDTree::remove(&tree, "a").await);
+
+        let guard = tree.read().await;
+        assert_eq!(guard.size, 1);
+        assert_eq!(guard.id, "a");
+    }
 }